@@ -1,7 +1,25 @@
-use embassy_stm32::gpio::{Flex, Level, Output, Pin, Pull, Speed};
-use embassy_stm32::{into_ref, Peripheral};
+use embedded_hal::delay::DelayNs;
+use embedded_hal::digital::{InputPin, OutputPin};
 
+mod error;
+mod font;
+pub(crate) mod fx;
 mod instructions;
+pub(crate) mod keys;
+mod pins;
+#[cfg(feature = "stm32")]
+mod stm32;
+mod transport;
+
+pub use error::TmError;
+pub(crate) use pins::NoPin;
+#[cfg(feature = "stm32")]
+pub use stm32::new_stm32;
+use transport::Transport;
+
+// Default CLK half-period / STB settling time, comfortably inside the
+// TM1638's minimum clock-pulse width even on slow MCUs.
+const DEFAULT_DELAY_US: u16 = 1;
 
 /*
  TODO:
@@ -14,70 +32,118 @@ mod instructions;
   • def_pressed_keys - разобраться (как вернуть массив из функции?)       [+]
  */
 
-pub struct LedAndKey<'d, STB: Pin, CLK: Pin, DIO: Pin> {
-    stb: Output<'d, STB>,
-    clk: Output<'d, CLK>,
-    dio: Flex<'d, DIO>,
+pub struct LedAndKey<STB, CLK, DIO, DELAY>
+where
+    STB: OutputPin,
+    CLK: OutputPin,
+    DIO: InputPin + OutputPin,
+    DELAY: DelayNs,
+{
+    stb: STB,
+    clk: CLK,
+    dio: DIO,
+    delay: DELAY,
+    delay_us: u16,
     display: bool,
     brightness: u8,
+    cursor: u8,
+    transport: Transport,
 }
 
-impl<'d, STB: Pin, CLK: Pin, DIO: Pin> LedAndKey<'d, STB, CLK, DIO> {
-    pub(crate) fn new(stb: impl Peripheral<P=STB> + 'static,
-                      clk: impl Peripheral<P=CLK> + 'static,
-                      dio: impl Peripheral<P=DIO> + 'static) -> LedAndKey<'d, STB, CLK, DIO> {
-        into_ref!(stb, clk, dio);
-
-        let mut clk: Output<CLK> = Output::new(clk, Level::Low, Speed::Low);
-        let mut dio: Flex<DIO> = Flex::new(dio);
-        let mut stb: Output<STB> = Output::new(stb, Level::Low, Speed::Low);
-        let mut display: bool = true;
-        let mut brightness: u8 = instructions::BRIGHTNESS;
+impl<STB, CLK, DIO, DELAY> LedAndKey<STB, CLK, DIO, DELAY>
+where
+    STB: OutputPin,
+    CLK: OutputPin,
+    DIO: InputPin + OutputPin,
+    DELAY: DelayNs,
+{
+    pub(crate) fn new(stb: STB, clk: CLK, dio: DIO, delay: DELAY) -> Result<Self, TmError<STB, CLK, DIO>> {
+        Self::new_with_transport(stb, clk, dio, delay, Transport::ThreeWire)
+    }
 
-        stb.set_high();
-        dio.set_low();
-        clk.set_low();
-        dio.set_as_output(Speed::Low); // By default, in data transfer mode.
+    pub(crate) fn new_with_transport(
+        stb: STB,
+        clk: CLK,
+        dio: DIO,
+        delay: DELAY,
+        transport: Transport,
+    ) -> Result<Self, TmError<STB, CLK, DIO>> {
+        let mut driver = Self {
+            stb,
+            clk,
+            dio,
+            delay,
+            delay_us: DEFAULT_DELAY_US,
+            display: true,
+            brightness: instructions::BRIGHTNESS,
+            cursor: 0,
+            transport,
+        };
+
+        driver.stb.set_high().map_err(TmError::Stb)?;
+        driver.settle();
+        driver.dio.set_low().map_err(TmError::Dio)?;
+        driver.clk.set_low().map_err(TmError::Clk)?;
+
+        driver.push_display_ctrl_instr()?;
+        driver.cleanup()?;
+
+        Ok(driver)
+    }
 
-        let mut driver = Self { stb, dio, clk, display, brightness };
-        driver.push_display_ctrl_instr();
-        driver.cleanup();
+    /*
+     Sets the delay observed after every CLK edge and around each STB
+     strobe. Tune this up on fast MCUs/long wires if reads or writes become
+     unreliable, per the TM1638's minimum clock-pulse timing.
+     @us: microseconds
+     */
+    pub(crate) fn set_delay_us(&mut self, us: u16) -> () {
+        self.delay_us = us;
+    }
 
-        driver
+    fn settle(&mut self) -> () {
+        self.delay.delay_us(self.delay_us as u32);
     }
 
     // Includes display.
-    pub(crate) fn display_on(&mut self) -> () {
+    pub(crate) fn display_on(&mut self) -> Result<(), TmError<STB, CLK, DIO>> {
         self.display = true;
-        self.push_display_ctrl_instr();
+        self.push_display_ctrl_instr()
     }
 
     // Disable display.
-    pub(crate) fn display_off(&mut self) -> () {
+    pub(crate) fn display_off(&mut self) -> Result<(), TmError<STB, CLK, DIO>> {
         self.display = false;
-        self.push_display_ctrl_instr();
+        self.push_display_ctrl_instr()
     }
 
     // Sets all display registers to zero.
-    pub(crate) fn cleanup(&mut self) -> () {
-        self.push_data_write_instr();
-        self.stb.set_low();
-        self.push_address_instr(instructions::NULL);
-
-        for i in 0..15 {
-            self.write_byte(instructions::NULL);
+    pub(crate) fn cleanup(&mut self) -> Result<(), TmError<STB, CLK, DIO>> {
+        self.push_data_write_instr()?;
+        self.begin_frame()?;
+        self.push_address_instr(instructions::NULL)?;
+
+        // TM1638 has 16 display registers, the TM1637 only 6; writing past
+        // the TM1637's last register gets it NACKed.
+        let register_count = match self.transport {
+            Transport::ThreeWire => 16,
+            Transport::TwoWire => 6,
+        };
+
+        for _ in 0..register_count {
+            self.send_byte(instructions::NULL)?;
         }
 
-        self.stb.set_low();
+        self.end_frame()
     }
 
     /*
      Sets the brightness of the LEDs and segments.
      @value: 0..7
      */
-    pub(crate) fn set_brightness(&mut self, value: u8) -> () {
+    pub(crate) fn set_brightness(&mut self, value: u8) -> Result<(), TmError<STB, CLK, DIO>> {
         self.brightness = value;
-        self.push_display_ctrl_instr();
+        self.push_display_ctrl_instr()
     }
 
     /*
@@ -85,8 +151,8 @@ impl<'d, STB: Pin, CLK: Pin, DIO: Pin> LedAndKey<'d, STB, CLK, DIO> {
      @position: 0..7
      @state: 0..9 and A-Z
      */
-    pub(crate) fn set_segment_value(&mut self, position: u8, value: u8) -> () {
-        self.write(position << 1, value);
+    pub(crate) fn set_segment_value(&mut self, position: u8, value: u8) -> Result<(), TmError<STB, CLK, DIO>> {
+        self.write(position << 1, value)
     }
 
     /*
@@ -94,49 +160,138 @@ impl<'d, STB: Pin, CLK: Pin, DIO: Pin> LedAndKey<'d, STB, CLK, DIO> {
      @position: 0..7
      @state: 0 or 1
      */
-    pub(crate) fn set_led_state(&mut self, position: u8, state: u8) -> () {
-        self.write((position << 1) + 1, state);
+    pub(crate) fn set_led_state(&mut self, position: u8, state: u8) -> Result<(), TmError<STB, CLK, DIO>> {
+        self.write((position << 1) + 1, state)
+    }
+
+    /*
+     Sets the character displayed at a digit position.
+     @position: 0..7
+     @c: any ASCII char; unrenderable chars are shown blank.
+     */
+    pub(crate) fn set_char(&mut self, position: u8, c: char) -> Result<(), TmError<STB, CLK, DIO>> {
+        self.set_segment_value(position, font::char_to_segments(c))
+    }
+
+    /*
+     Renders a string left-to-right starting at @position, one char per digit.
+     A '.' is merged into the decimal point of the previously written digit
+     instead of consuming a digit position of its own. Stops once the eight
+     digit grids are filled.
+     */
+    pub(crate) fn write_str_at(&mut self, position: u8, s: &str) -> Result<(), TmError<STB, CLK, DIO>> {
+        self.push_str_from(position, s)?;
+        Ok(())
+    }
+
+    /*
+     Moves the cursor used by `core::fmt::Write` back to digit 0.
+     Call this before a `write!`/`writeln!` that should start from the
+     beginning of the display rather than continuing after the previous one.
+     */
+    pub(crate) fn reset_cursor(&mut self) -> () {
+        self.cursor = 0;
+    }
+
+    // Shared by `write_str_at` and the `core::fmt::Write` impl: writes `s`
+    // starting at digit `start`, returning the next free digit position.
+    fn push_str_from(&mut self, start: u8, s: &str) -> Result<u8, TmError<STB, CLK, DIO>> {
+        let mut pos = start;
+        let mut last_byte: u8 = 0;
+        let mut has_prev = false;
+
+        for c in s.chars() {
+            // Checked before the bounds break: a trailing '.' merges into
+            // the already-written previous digit rather than consuming a
+            // position of its own, so it's still valid once `pos` has run
+            // off the end of the eight digit grids.
+            if c == '.' && has_prev {
+                last_byte |= font::DP;
+                self.set_segment_value(pos - 1, last_byte)?;
+                continue;
+            }
+
+            if pos > 7 {
+                break;
+            }
+
+            last_byte = font::char_to_segments(c);
+            self.set_segment_value(pos, last_byte)?;
+            pos += 1;
+            has_prev = true;
+        }
+
+        Ok(pos)
     }
 
     /*
      Determines the key pressed.
      Returns an array of states for each key, from left to right: true - pressed, false - otherwise.
     */
-    pub(crate) fn def_pressed_keys<'a>(&'a mut self, keys_array: &'a mut [bool; 8]) -> &mut [bool; 8] {
-        let mut data: u32 = self.scan_keys();
+    pub(crate) fn def_pressed_keys<'a>(&'a mut self, keys_array: &'a mut [bool; 8]) -> Result<&mut [bool; 8], TmError<STB, CLK, DIO>> {
+        let bits = self.raw_key_bits()?;
+
+        for i in 0..8 {
+            keys_array[i] = (bits >> i) & 1 == 1;
+        }
+
+        Ok(keys_array)
+    }
+
+    // Packs the 8 key states from a raw `scan_keys` read into a single
+    // bitmask, bit i set when key i is pressed. Shared by `def_pressed_keys`
+    // and `keys::KeyState::poll`.
+    pub(crate) fn raw_key_bits(&mut self) -> Result<u8, TmError<STB, CLK, DIO>> {
+        let data = self.scan_keys()?;
+        let mut bits: u8 = 0;
 
         for i in 0..4 {
-            keys_array[i] = if (data >> (8 * i) & 1) == 1 { true } else { false };
-            keys_array[i + 4] = if (data >> (8 * i + 4) & 1) == 1 { true } else { false };
+            if (data >> (8 * i) & 1) == 1 {
+                bits |= 1 << i;
+            }
+            if (data >> (8 * i + 4) & 1) == 1 {
+                bits |= 1 << (i + 4);
+            }
         }
 
-        keys_array
+        Ok(bits)
     }
 
     /*
      Write a byte to the display register.
      @position: 0..15
      */
-    fn write(&mut self, position: u8, data: u8) -> () {
-        self.push_data_write_instr();
+    fn write(&mut self, position: u8, data: u8) -> Result<(), TmError<STB, CLK, DIO>> {
+        self.push_data_write_instr()?;
 
-        self.stb.set_low();
-        self.push_address_instr(position);
-        self.write_byte(data);
-        self.stb.set_high();
+        self.begin_frame()?;
+        self.push_address_instr(position)?;
+        self.send_byte(data)?;
+        self.end_frame()
     }
 
     // Reads the values of each button.
-    pub(crate) fn scan_keys(&mut self) -> u32 {
-        self.stb.set_low();
-        self.write_byte(instructions::SET_DATA_INSTR | instructions::DATA_READ_INSTR);
+    //
+    // TM1638-only: the TM1637 reads back a single key byte over a different
+    // start/ack/stop sequence than this 4-byte scan, so this returns
+    // `TmError::Unsupported` on the 2-wire transport rather than silently
+    // misreading the bus.
+    pub(crate) fn scan_keys(&mut self) -> Result<u32, TmError<STB, CLK, DIO>> {
+        if let Transport::TwoWire = self.transport {
+            return Err(TmError::Unsupported);
+        }
+
+        self.begin_frame()?;
+        self.send_byte(instructions::SET_DATA_INSTR | instructions::DATA_READ_INSTR)?;
 
         let mut data: u32 = 0;
-        for i in 0..4 { data |= (self.read_byte() as u32) << (i * 8); }
+        for i in 0..4 {
+            data |= (self.read_byte()? as u32) << (i * 8);
+        }
 
-        self.stb.set_high();
+        self.end_frame()?;
 
-        data
+        Ok(data)
     }
 
     /*
@@ -145,10 +300,11 @@ impl<'d, STB: Pin, CLK: Pin, DIO: Pin> LedAndKey<'d, STB, CLK, DIO> {
      ~ display on
      ~ brightness max (0x07)
      */
-    fn push_display_ctrl_instr(&mut self) -> () {
-        self.stb.set_high();
-        self.dio.set_low();
-        self.clk.set_low();
+    fn push_display_ctrl_instr(&mut self) -> Result<(), TmError<STB, CLK, DIO>> {
+        self.stb.set_high().map_err(TmError::Stb)?;
+        self.settle();
+        self.dio.set_low().map_err(TmError::Dio)?;
+        self.clk.set_low().map_err(TmError::Clk)?;
 
         let display_instr: u8;
 
@@ -159,55 +315,189 @@ impl<'d, STB: Pin, CLK: Pin, DIO: Pin> LedAndKey<'d, STB, CLK, DIO> {
         }
 
         self.push_instruction(instructions::SET_DISPLAY_CTRL_INSTR |
-            display_instr | self.brightness);
+            display_instr | self.brightness)
     }
 
     /*
      Sends instructions for subsequent recording.
      Data command: AUTOMATIC address increment, normal mode.
      */
-    fn push_data_write_instr(&mut self) -> () {
+    fn push_data_write_instr(&mut self) -> Result<(), TmError<STB, CLK, DIO>> {
         self.push_instruction(instructions::SET_DATA_INSTR |
-            instructions::DATA_WRITE_INSTR);
+            instructions::DATA_WRITE_INSTR)
     }
 
     // Sets the address to write the value to.
-    fn push_address_instr(&mut self, address: u8) -> () {
-        self.write_byte(instructions::SET_ADDRESS_INSTR | address);
+    fn push_address_instr(&mut self, address: u8) -> Result<(), TmError<STB, CLK, DIO>> {
+        self.send_byte(instructions::SET_ADDRESS_INSTR | address)
     }
 
-    // Push a instruction to the TM1638.
-    fn push_instruction(&mut self, instruction: u8) -> () {
-        self.stb.set_low();
-        self.write_byte(instruction);
-        self.stb.set_high();
+    // Push a instruction to the TM1638/TM1637.
+    fn push_instruction(&mut self, instruction: u8) -> Result<(), TmError<STB, CLK, DIO>> {
+        self.begin_frame()?;
+        self.send_byte(instruction)?;
+        self.end_frame()
+    }
+
+    /*
+     Opens a logical write frame: an STB strobe low on the 3-wire TM1638, or
+     a start condition (DIO falling while CLK is high) on the 2-wire TM1637.
+     */
+    fn begin_frame(&mut self) -> Result<(), TmError<STB, CLK, DIO>> {
+        match self.transport {
+            Transport::ThreeWire => {
+                self.stb.set_low().map_err(TmError::Stb)?;
+                self.settle();
+                Ok(())
+            }
+            Transport::TwoWire => self.start(),
+        }
+    }
+
+    /*
+     Closes a logical write frame opened by `begin_frame`.
+     */
+    fn end_frame(&mut self) -> Result<(), TmError<STB, CLK, DIO>> {
+        match self.transport {
+            Transport::ThreeWire => {
+                self.stb.set_high().map_err(TmError::Stb)?;
+                self.settle();
+                Ok(())
+            }
+            Transport::TwoWire => self.stop(),
+        }
+    }
+
+    // Writes a byte within a frame, sampling the TM1637's ACK bit when the
+    // 2-wire transport is in use. No-op on the 3-wire transport beyond the
+    // raw bit-bang, since the TM1638 has no ACK phase.
+    fn send_byte(&mut self, byte: u8) -> Result<(), TmError<STB, CLK, DIO>> {
+        self.write_byte(byte)?;
+
+        if let Transport::TwoWire = self.transport {
+            self.read_ack()?;
+        }
+
+        Ok(())
+    }
+
+    // TM1637 framing: a start condition is DIO falling while CLK is high.
+    fn start(&mut self) -> Result<(), TmError<STB, CLK, DIO>> {
+        self.dio.set_high().map_err(TmError::Dio)?;
+        self.clk.set_high().map_err(TmError::Clk)?;
+        self.settle();
+        self.dio.set_low().map_err(TmError::Dio)?;
+        self.settle();
+
+        Ok(())
+    }
+
+    // TM1637 framing: a stop condition is DIO rising while CLK is high.
+    fn stop(&mut self) -> Result<(), TmError<STB, CLK, DIO>> {
+        self.dio.set_low().map_err(TmError::Dio)?;
+        self.clk.set_low().map_err(TmError::Clk)?;
+        self.settle();
+        self.clk.set_high().map_err(TmError::Clk)?;
+        self.settle();
+        self.dio.set_high().map_err(TmError::Dio)?;
+        self.settle();
+
+        Ok(())
+    }
+
+    // TM1637 framing: after the 8th bit, release DIO and clock one more
+    // pulse to sample the chip's ACK (DIO pulled low by the chip = acked).
+    fn read_ack(&mut self) -> Result<(), TmError<STB, CLK, DIO>> {
+        self.clk.set_low().map_err(TmError::Clk)?;
+        self.dio.set_high().map_err(TmError::Dio)?;
+        self.settle();
+        self.clk.set_high().map_err(TmError::Clk)?;
+        self.settle();
+
+        let acked = !self.dio.is_high().map_err(TmError::Dio)?;
+
+        self.clk.set_low().map_err(TmError::Clk)?;
+        self.settle();
+
+        if acked {
+            Ok(())
+        } else {
+            Err(TmError::Nack)
+        }
     }
 
     // Write 1 byte of information to the TM1638.
-    fn write_byte(&mut self, byte: u8) -> () {
+    fn write_byte(&mut self, byte: u8) -> Result<(), TmError<STB, CLK, DIO>> {
         for i in 0..8 {
-            self.clk.set_low();
+            self.clk.set_low().map_err(TmError::Clk)?;
+            self.settle();
 
-            if (byte >> i) & 1 == 0 { self.dio.set_low(); } else { self.dio.set_high(); }
+            if (byte >> i) & 1 == 0 {
+                self.dio.set_low().map_err(TmError::Dio)?;
+            } else {
+                self.dio.set_high().map_err(TmError::Dio)?;
+            }
 
-            self.clk.set_high();
+            self.clk.set_high().map_err(TmError::Clk)?;
+            self.settle();
         }
+
+        Ok(())
     }
 
     // Read 1 byte of information from TM1638.
-    fn read_byte(&mut self) -> u8 {
-        self.dio.set_as_input(Pull::Up);
+    fn read_byte(&mut self) -> Result<u8, TmError<STB, CLK, DIO>> {
+        // DIO is open-drain on the wire; releasing it high lets the TM1638
+        // drive it low for a 0 bit without us switching pin direction.
+        self.dio.set_high().map_err(TmError::Dio)?;
 
         let mut byte: u8 = 0;
         for i in 0..8 {
-            self.clk.set_low();
-            self.clk.set_high();
-
-            if self.dio.is_high() { byte |= 1 << i; }
+            self.clk.set_low().map_err(TmError::Clk)?;
+            self.settle();
+            self.clk.set_high().map_err(TmError::Clk)?;
+            self.settle();
+
+            if self.dio.is_high().map_err(TmError::Dio)? {
+                byte |= 1 << i;
+            }
         }
 
-        self.dio.set_as_output(Speed::Low);
+        Ok(byte)
+    }
+}
+
+// Constructor for the TM1637's 2-wire variant (CLK+DIO, no STB): there is no
+// physical strobe line, so it's stood in for with `NoPin` and framing is
+// driven by I2C-like start/stop conditions instead of an STB strobe.
+impl<CLK, DIO, DELAY> LedAndKey<NoPin, CLK, DIO, DELAY>
+where
+    CLK: OutputPin,
+    DIO: InputPin + OutputPin,
+    DELAY: DelayNs,
+{
+    pub(crate) fn new_tm1637(clk: CLK, dio: DIO, delay: DELAY) -> Result<Self, TmError<NoPin, CLK, DIO>> {
+        Self::new_with_transport(NoPin, clk, dio, delay, Transport::TwoWire)
+    }
+}
 
-        byte
+// Lets callers drive the display with `write!`/`writeln!` instead of looping
+// over positions themselves, e.g. `write!(display, "{:>4}", count)`. The
+// write position is a persistent cursor that only rewinds to digit 0 on
+// `reset_cursor`, since `core::fmt::Write::write_str` may be called more
+// than once per `write!` (once per literal/argument) and resetting on every
+// call would overwrite earlier arguments of the same format string. Pin
+// errors can't be represented in `core::fmt::Result`, so they collapse to
+// `core::fmt::Error`.
+impl<STB, CLK, DIO, DELAY> core::fmt::Write for LedAndKey<STB, CLK, DIO, DELAY>
+where
+    STB: OutputPin,
+    CLK: OutputPin,
+    DIO: InputPin + OutputPin,
+    DELAY: DelayNs,
+{
+    fn write_str(&mut self, s: &str) -> core::fmt::Result {
+        self.cursor = self.push_str_from(self.cursor, s).map_err(|_| core::fmt::Error)?;
+        Ok(())
     }
 }
@@ -0,0 +1,44 @@
+use core::fmt;
+use embedded_hal::digital::{ErrorType, InputPin, OutputPin};
+
+// Wraps whichever GPIO line failed so callers get one error type regardless
+// of which of the three pins (STB, CLK, DIO) raised it.
+pub enum TmError<STB, CLK, DIO>
+where
+    STB: OutputPin,
+    CLK: OutputPin,
+    DIO: InputPin + OutputPin,
+{
+    Stb(<STB as ErrorType>::Error),
+    Clk(<CLK as ErrorType>::Error),
+    Dio(<DIO as ErrorType>::Error),
+    // 2-wire (TM1637) transport only: the chip didn't pull DIO low to
+    // acknowledge a byte.
+    Nack,
+    // The requested operation has no defined sequence on the active
+    // transport (e.g. `scan_keys` on the 2-wire TM1637, which reads a
+    // single key byte over a different start/ack/stop sequence than the
+    // TM1638's 4-byte scan).
+    Unsupported,
+}
+
+// Hand-written rather than derived: a derive bounds STB/CLK/DIO themselves
+// on `Debug`, but `new_stm32`'s pins (`embassy_stm32::gpio::Output`/`Flex`)
+// aren't `Debug`. Only the associated `ErrorType::Error` types need to be,
+// and `embedded-hal` already requires that via its supertrait.
+impl<STB, CLK, DIO> fmt::Debug for TmError<STB, CLK, DIO>
+where
+    STB: OutputPin,
+    CLK: OutputPin,
+    DIO: InputPin + OutputPin,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Stb(e) => f.debug_tuple("Stb").field(e).finish(),
+            Self::Clk(e) => f.debug_tuple("Clk").field(e).finish(),
+            Self::Dio(e) => f.debug_tuple("Dio").field(e).finish(),
+            Self::Nack => write!(f, "Nack"),
+            Self::Unsupported => write!(f, "Unsupported"),
+        }
+    }
+}
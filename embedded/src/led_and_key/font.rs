@@ -0,0 +1,83 @@
+// 7-segment bit layout: a=bit0, b=bit1, c=bit2, d=bit3, e=bit4, f=bit5, g=bit6, dp=bit7.
+
+pub(crate) const DP: u8 = 0b1000_0000;
+
+const BLANK: u8 = 0b0000_0000;
+
+// ASCII-indexed lookup table covering 0-9, A-Z, a-z (where renderable), space, '-', '_', '.'.
+const TABLE: [u8; 128] = build_table();
+
+const fn build_table() -> [u8; 128] {
+    let mut table = [BLANK; 128];
+
+    table[' ' as usize] = 0b0000_0000;
+    table['-' as usize] = 0b0100_0000;
+    table['_' as usize] = 0b0000_1000;
+    table['.' as usize] = DP;
+
+    table['0' as usize] = 0b0011_1111;
+    table['1' as usize] = 0b0000_0110;
+    table['2' as usize] = 0b0101_1011;
+    table['3' as usize] = 0b0100_1111;
+    table['4' as usize] = 0b0110_0110;
+    table['5' as usize] = 0b0110_1101;
+    table['6' as usize] = 0b0111_1101;
+    table['7' as usize] = 0b0000_0111;
+    table['8' as usize] = 0b0111_1111;
+    table['9' as usize] = 0b0110_1111;
+
+    table['A' as usize] = 0b0111_0111;
+    table['a' as usize] = 0b0111_0111;
+    table['B' as usize] = 0b0111_1111; // identical to 8; the lower case 'b' glyph is distinct
+    table['b' as usize] = 0b0111_1100;
+    table['C' as usize] = 0b0011_1001;
+    table['c' as usize] = 0b0101_1000;
+    table['D' as usize] = 0b0011_1111; // identical to 0; the lower case 'd' glyph is distinct
+    table['d' as usize] = 0b0101_1110;
+    table['E' as usize] = 0b0111_1001;
+    table['e' as usize] = 0b0111_1001;
+    table['F' as usize] = 0b0111_0001;
+    table['f' as usize] = 0b0111_0001;
+    table['G' as usize] = 0b0011_1101;
+    table['g' as usize] = 0b0110_1111;
+    table['H' as usize] = 0b0111_0110;
+    table['h' as usize] = 0b0111_0100;
+    table['I' as usize] = 0b0000_0110;
+    table['i' as usize] = 0b0000_0100;
+    table['J' as usize] = 0b0001_1110;
+    table['j' as usize] = 0b0001_1110;
+    table['L' as usize] = 0b0011_1000;
+    table['l' as usize] = 0b0011_0000;
+    table['N' as usize] = 0b0101_0100; // best-effort approximation
+    table['n' as usize] = 0b0101_0100;
+    table['O' as usize] = 0b0011_1111;
+    table['o' as usize] = 0b0101_1100;
+    table['P' as usize] = 0b0111_0011;
+    table['p' as usize] = 0b0111_0011;
+    table['Q' as usize] = 0b0110_0111;
+    table['q' as usize] = 0b0110_0111;
+    table['R' as usize] = 0b0101_0000;
+    table['r' as usize] = 0b0101_0000;
+    table['S' as usize] = 0b0110_1101; // identical to 5
+    table['s' as usize] = 0b0110_1101;
+    table['T' as usize] = 0b0111_1000;
+    table['t' as usize] = 0b0111_1000;
+    table['U' as usize] = 0b0011_1110;
+    table['u' as usize] = 0b0001_1100;
+    table['Y' as usize] = 0b0110_1110;
+    table['y' as usize] = 0b0110_1110;
+    table['Z' as usize] = 0b0101_1011; // identical to 2
+    table['z' as usize] = 0b0101_1011;
+
+    table
+}
+
+// Maps an ASCII character to its 7-segment pattern, substituting a blank
+// glyph for anything that has no reasonable representation on a digit.
+pub(crate) fn char_to_segments(c: char) -> u8 {
+    if (c as u32) < 128 {
+        TABLE[c as usize]
+    } else {
+        BLANK
+    }
+}
@@ -0,0 +1,135 @@
+use embedded_hal::delay::DelayNs;
+use embedded_hal::digital::{InputPin, OutputPin};
+
+use super::error::TmError;
+use super::LedAndKey;
+
+// Tick-driven display effects built on top of `set_char`/`set_segment_value`.
+// Each effect is allocation-free and advances only when the caller calls
+// `step`, so timing stays entirely under the caller's control.
+
+pub(crate) enum MarqueeMode {
+    // Loops back to the start once the text scrolls off the end.
+    Wrap,
+    // Reverses direction at each end instead of looping.
+    Bounce,
+}
+
+// Scrolls ASCII text across the eight digit grids, one position per `step`.
+pub(crate) struct Marquee<'a> {
+    text: &'a str,
+    mode: MarqueeMode,
+    position: usize,
+}
+
+impl<'a> Marquee<'a> {
+    pub(crate) fn new(text: &'a str, mode: MarqueeMode) -> Self {
+        Self { text, mode, position: 0 }
+    }
+
+    pub(crate) fn step<STB, CLK, DIO, DELAY>(
+        &mut self,
+        dev: &mut LedAndKey<STB, CLK, DIO, DELAY>,
+    ) -> Result<(), TmError<STB, CLK, DIO>>
+    where
+        STB: OutputPin,
+        CLK: OutputPin,
+        DIO: InputPin + OutputPin,
+        DELAY: DelayNs,
+    {
+        let glyphs = self.text.as_bytes();
+        let len = glyphs.len();
+
+        if len == 0 {
+            return Ok(());
+        }
+
+        for slot in 0..8u8 {
+            let idx = self.visible_index(slot as usize, len);
+            dev.set_char(slot, glyphs[idx] as char)?;
+        }
+
+        self.advance(len);
+        Ok(())
+    }
+
+    fn visible_index(&self, slot: usize, len: usize) -> usize {
+        match self.mode {
+            MarqueeMode::Wrap => (self.position + slot) % len,
+            MarqueeMode::Bounce => {
+                // Fold the window's start offset as a single unit, then lay
+                // the rest of the 8-slot window on top of it, so the whole
+                // window ramps up then back down together instead of each
+                // slot reflecting off the end independently (which showed a
+                // mirror image inside one frame near a turning point).
+                let span = len.saturating_sub(8);
+                let base = Self::triangle(self.position, span + 1);
+                (base + slot) % len
+            }
+        }
+    }
+
+    fn advance(&mut self, len: usize) {
+        let period = match self.mode {
+            MarqueeMode::Wrap => len,
+            MarqueeMode::Bounce => (2 * len.saturating_sub(8)).max(1),
+        };
+
+        self.position = (self.position + 1) % period;
+    }
+
+    // Folds a straight-line position back and forth over 0..len, giving the
+    // 0, 1, .., len-1, len-2, .., 1, 0, 1, .. sequence bounce mode needs.
+    fn triangle(position: usize, len: usize) -> usize {
+        if len <= 1 {
+            return 0;
+        }
+
+        let period = 2 * (len - 1);
+        let p = position % period;
+
+        if p < len {
+            p
+        } else {
+            period - p
+        }
+    }
+}
+
+// The six outer-ring segments, in display order, used by `Spinner`.
+const RING: [u8; 6] = [
+    0b0000_0001, // a
+    0b0000_0010, // b
+    0b0000_0100, // c
+    0b0000_1000, // d
+    0b0001_0000, // e
+    0b0010_0000, // f
+];
+
+// Cycles a single digit through the outer-ring segments to show activity.
+pub(crate) struct Spinner {
+    position: u8,
+    phase: u8,
+}
+
+impl Spinner {
+    pub(crate) fn new(position: u8) -> Self {
+        Self { position, phase: 0 }
+    }
+
+    pub(crate) fn step<STB, CLK, DIO, DELAY>(
+        &mut self,
+        dev: &mut LedAndKey<STB, CLK, DIO, DELAY>,
+    ) -> Result<(), TmError<STB, CLK, DIO>>
+    where
+        STB: OutputPin,
+        CLK: OutputPin,
+        DIO: InputPin + OutputPin,
+        DELAY: DelayNs,
+    {
+        dev.set_segment_value(self.position, RING[self.phase as usize])?;
+        self.phase = (self.phase + 1) % RING.len() as u8;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,71 @@
+use embedded_hal::delay::DelayNs;
+use embedded_hal::digital::{InputPin, OutputPin};
+
+use super::error::TmError;
+use super::LedAndKey;
+
+// Consecutive identical raw reads required before a key's debounced state
+// flips; tune up via `with_debounce_count` for noisier switches.
+const DEFAULT_DEBOUNCE_COUNT: u8 = 3;
+
+// Press/release edge bitmasks for the 8 keys since the previous `poll`.
+pub(crate) struct KeyEvents {
+    pub(crate) pressed: u8,
+    pub(crate) released: u8,
+}
+
+// N-sample integrator debouncer sitting on top of `scan_keys`: a key only
+// flips once it reads consistently high (or low) for `debounce_count`
+// consecutive polls.
+pub(crate) struct KeyState {
+    debounced: u8,
+    counters: [u8; 8],
+    debounce_count: u8,
+}
+
+impl KeyState {
+    pub(crate) fn new() -> Self {
+        Self::with_debounce_count(DEFAULT_DEBOUNCE_COUNT)
+    }
+
+    pub(crate) fn with_debounce_count(debounce_count: u8) -> Self {
+        Self { debounced: 0, counters: [0; 8], debounce_count }
+    }
+
+    pub(crate) fn poll<STB, CLK, DIO, DELAY>(
+        &mut self,
+        dev: &mut LedAndKey<STB, CLK, DIO, DELAY>,
+    ) -> Result<KeyEvents, TmError<STB, CLK, DIO>>
+    where
+        STB: OutputPin,
+        CLK: OutputPin,
+        DIO: InputPin + OutputPin,
+        DELAY: DelayNs,
+    {
+        let raw = dev.raw_key_bits()?;
+        let previous = self.debounced;
+
+        for key in 0..8u8 {
+            let bit = 1 << key;
+            let is_high = raw & bit != 0;
+            let was_high = self.debounced & bit != 0;
+
+            if is_high == was_high {
+                self.counters[key as usize] = 0;
+                continue;
+            }
+
+            self.counters[key as usize] += 1;
+
+            if self.counters[key as usize] >= self.debounce_count {
+                self.debounced ^= bit;
+                self.counters[key as usize] = 0;
+            }
+        }
+
+        Ok(KeyEvents {
+            pressed: !previous & self.debounced,
+            released: previous & !self.debounced,
+        })
+    }
+}
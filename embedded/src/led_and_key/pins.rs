@@ -0,0 +1,20 @@
+use core::convert::Infallible;
+use embedded_hal::digital::{ErrorType, OutputPin};
+
+// Stand-in STB for transports that have no physical strobe line (the
+// TM1637's 2-wire mode); every operation is a no-op that always succeeds.
+pub(crate) struct NoPin;
+
+impl ErrorType for NoPin {
+    type Error = Infallible;
+}
+
+impl OutputPin for NoPin {
+    fn set_low(&mut self) -> Result<(), Self::Error> {
+        Ok(())
+    }
+
+    fn set_high(&mut self) -> Result<(), Self::Error> {
+        Ok(())
+    }
+}
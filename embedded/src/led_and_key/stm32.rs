@@ -0,0 +1,34 @@
+use embassy_stm32::gpio::{Flex, Level, Output, Pin, Pull, Speed};
+use embassy_stm32::{into_ref, Peripheral};
+use embedded_hal::delay::DelayNs;
+
+use super::error::TmError;
+use super::LedAndKey;
+
+// Thin adapter for boards built on embassy-stm32: builds the `Output`/`Flex`
+// pins this driver used to be hard-coded to and hands them to the portable
+// `LedAndKey::new` through their `embedded-hal` impls.
+pub fn new_stm32<'d, STB: Pin, CLK: Pin, DIO: Pin, DELAY: DelayNs>(
+    stb: impl Peripheral<P = STB> + 'd,
+    clk: impl Peripheral<P = CLK> + 'd,
+    dio: impl Peripheral<P = DIO> + 'd,
+    delay: DELAY,
+) -> Result<
+    LedAndKey<Output<'d, STB>, Output<'d, CLK>, Flex<'d, DIO>, DELAY>,
+    TmError<Output<'d, STB>, Output<'d, CLK>, Flex<'d, DIO>>,
+> {
+    into_ref!(stb, clk, dio);
+
+    let stb = Output::new(stb, Level::High, Speed::Low);
+    let clk = Output::new(clk, Level::Low, Speed::Low);
+    let mut dio = Flex::new(dio);
+    // Open-drain with an internal pull-up, not push-pull: `read_byte`
+    // releases DIO with `set_high` and expects the TM1638 to be able to pull
+    // it low for a 0 bit, and `is_high` to reflect that instead of the
+    // MCU's own driven level. The pull-up is required here, not optional:
+    // most LED&KEY boards have no external one, so a released line would
+    // otherwise float instead of reading high.
+    dio.set_as_input_output_pull(Speed::Low, Pull::Up);
+
+    LedAndKey::new(stb, clk, dio, delay)
+}
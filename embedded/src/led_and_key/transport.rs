@@ -0,0 +1,7 @@
+// Selects which physical framing `LedAndKey` drives: the 3-wire TM1638
+// STB/CLK/DIO strobe, or the 2-wire TM1637 CLK/DIO start/stop protocol.
+#[derive(Clone, Copy)]
+pub(crate) enum Transport {
+    ThreeWire,
+    TwoWire,
+}